@@ -1,4 +1,4 @@
-use auto_update::{AutoUpdateStatus, AutoUpdater, DismissErrorMessage};
+use auto_update::{AutoUpdateStatus, AutoUpdater, DismissErrorMessage, InstallPhase};
 use editor::Editor;
 use extension::ExtensionStore;
 use futures::StreamExt;
@@ -255,37 +255,136 @@ impl ActivityIndicator {
 
         // Show any application auto-update info.
         if let Some(updater) = &self.auto_updater {
-            return match &updater.read(cx).status() {
+            let updater = updater.read(cx);
+            return match &updater.status() {
                 AutoUpdateStatus::Checking => Content {
                     icon: Some(DOWNLOAD_ICON),
                     message: "Checking for Zed updates…".to_string(),
                     on_click: None,
                 },
-                AutoUpdateStatus::Downloading => Content {
+                AutoUpdateStatus::UpdateAvailable => Content {
                     icon: Some(DOWNLOAD_ICON),
-                    message: "Downloading Zed update…".to_string(),
+                    message: match updater.available_version() {
+                        Some(version) => format!("Zed {version} is available"),
+                        None => "A Zed update is available".to_string(),
+                    },
                     on_click: None,
                 },
+                AutoUpdateStatus::Downloading { .. } => Content {
+                    icon: Some(DOWNLOAD_ICON),
+                    message: match updater.download_progress() {
+                        Some(progress) => {
+                            format!("Downloading Zed update… {:.0}%", progress * 100.)
+                        }
+                        None => "Downloading Zed update…".to_string(),
+                    },
+                    on_click: None,
+                },
+                AutoUpdateStatus::AwaitingConfirmation { size } => Content {
+                    icon: Some(DOWNLOAD_ICON),
+                    message: format!(
+                        "Click to download Zed update ({}MB)",
+                        size.div_ceil(1024 * 1024)
+                    ),
+                    on_click: Some(Arc::new(|this, cx| {
+                        if let Some(updater) = &this.auto_updater {
+                            updater.update(cx, |updater, cx| updater.confirm_download(cx));
+                        }
+                    })),
+                },
+                AutoUpdateStatus::ReadyToInstall => Content {
+                    icon: Some(DOWNLOAD_ICON),
+                    message: "Click to install Zed update".to_string(),
+                    on_click: Some(Arc::new(|this, cx| {
+                        if let Some(updater) = &this.auto_updater {
+                            updater.update(cx, |updater, cx| updater.install_now(cx));
+                        }
+                    })),
+                },
+                AutoUpdateStatus::WaitingForIdle => Content {
+                    icon: Some(DOWNLOAD_ICON),
+                    message: "Zed update will install when you're idle. Click to install now"
+                        .to_string(),
+                    on_click: Some(Arc::new(|this, cx| {
+                        if let Some(updater) = &this.auto_updater {
+                            updater.update(cx, |updater, cx| updater.install_now(cx));
+                        }
+                    })),
+                },
+                AutoUpdateStatus::UpdateAvailableButBlocked => Content {
+                    icon: Some(WARNING_ICON),
+                    message: "Click to install Zed update as an administrator".to_string(),
+                    on_click: Some(Arc::new(|this, cx| {
+                        if let Some(updater) = this.auto_updater.clone() {
+                            AutoUpdater::authorize_install(updater, cx).detach_and_log_err(cx);
+                        }
+                    })),
+                },
                 AutoUpdateStatus::Installing => Content {
                     icon: Some(DOWNLOAD_ICON),
-                    message: "Installing Zed update…".to_string(),
+                    message: match updater.install_phase() {
+                        Some(InstallPhase::Mounting) => "Opening Zed update…",
+                        Some(InstallPhase::Copying) => "Installing Zed update…",
+                        Some(InstallPhase::Unmounting) => "Finishing Zed update…",
+                        None => "Installing Zed update…",
+                    }
+                    .to_string(),
                     on_click: None,
                 },
+                AutoUpdateStatus::Updated | AutoUpdateStatus::PendingRestart
+                    if updater.restart_countdown().is_some() =>
+                {
+                    Content {
+                        icon: None,
+                        message: format!(
+                            "Restarting to update Zed in {}s. Click to cancel",
+                            updater.restart_countdown().unwrap_or_default()
+                        ),
+                        on_click: Some(Arc::new(|this, cx| {
+                            if let Some(updater) = &this.auto_updater {
+                                updater.update(cx, |updater, cx| updater.cancel_restart(cx));
+                            }
+                        })),
+                    }
+                }
                 AutoUpdateStatus::Updated => Content {
                     icon: None,
                     message: "Click to restart and update Zed".to_string(),
+                    on_click: Some(Arc::new(|_, cx| auto_update::restart_to_update(cx))),
+                },
+                AutoUpdateStatus::PendingRestart => Content {
+                    icon: None,
+                    message: "Click to restart and finish updating Zed".to_string(),
+                    on_click: Some(Arc::new(|_, cx| auto_update::restart_to_update(cx))),
+                },
+                AutoUpdateStatus::RolledBack => Content {
+                    icon: None,
+                    message: "Click to restart into the previous version of Zed".to_string(),
                     on_click: Some(Arc::new(|_, cx| {
                         workspace::restart(&Default::default(), cx)
                     })),
                 },
-                AutoUpdateStatus::Errored => Content {
+                AutoUpdateStatus::Errored(error) => Content {
                     icon: Some(WARNING_ICON),
-                    message: "Auto update failed".to_string(),
+                    message: format!("Auto update failed. {}", error.remediation()),
                     on_click: Some(Arc::new(|this, cx| {
                         this.dismiss_error_message(&Default::default(), cx)
                     })),
                 },
-                AutoUpdateStatus::Idle => Default::default(),
+                AutoUpdateStatus::CannotAutoUpdate(reason) if reason.can_move_to_applications() => {
+                    Content {
+                        icon: Some(WARNING_ICON),
+                        message: "Click to move Zed to Applications and keep it up to date"
+                            .to_string(),
+                        on_click: Some(Arc::new(|_, cx| auto_update::move_to_applications(cx))),
+                    }
+                }
+                AutoUpdateStatus::CannotAutoUpdate(reason) => Content {
+                    icon: Some(WARNING_ICON),
+                    message: format!("Zed can't update itself. {}", reason.remediation()),
+                    on_click: None,
+                },
+                AutoUpdateStatus::Idle | AutoUpdateStatus::Offline => Default::default(),
             };
         }
 
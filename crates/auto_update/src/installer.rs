@@ -0,0 +1,1981 @@
+use crate::{remove_dir_if_exists, InstallPhase, UpdateError};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use client::ZED_APP_PATH;
+use gpui::{AppContext, AsyncAppContext, SemanticVersion};
+use release_channel::ReleaseChannel;
+use smol::{process::Command, stream::StreamExt};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt;
+
+/// The Apple Developer Team ID that updates must be signed by. Builds signed
+/// by another team can supply theirs by setting `ZED_UPDATE_TEAM_ID` when
+/// building, or set it to an empty string to accept any valid signature.
+#[cfg(target_os = "macos")]
+const EXPECTED_TEAM_ID: &str = match option_env!("ZED_UPDATE_TEAM_ID") {
+    Some(team_id) => team_id,
+    None => "MQ55VZLNZQ",
+};
+
+/// Puts a downloaded update in place of the running app.
+#[async_trait]
+pub trait Installer: Send + Sync {
+    /// The app that updates are installed over.
+    fn app_path(&self, cx: &AsyncAppContext) -> Result<PathBuf>;
+
+    /// Installs the update asset at `asset_path` over the app at `app_path`,
+    /// calling `report_phase` as each phase of the install starts.
+    async fn install(
+        &self,
+        asset_path: &Path,
+        app_path: &Path,
+        release_channel: ReleaseChannel,
+        verify_signature: bool,
+        report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()>;
+
+    /// Whether installing over the app at `app_path` needs administrator
+    /// privileges, because the current user can't write to it.
+    fn requires_privileges(&self, app_path: &Path) -> bool;
+
+    /// The version of the app at `app_path` on disk, if it can be read. It's
+    /// newer than the running version if another instance of Zed has
+    /// installed an update over it.
+    fn installed_version(&self, app_path: &Path) -> Option<SemanticVersion>;
+
+    /// Whether the app at `app_path` is outside the Applications folder, so
+    /// the user should be offered to move it there before it's updated.
+    fn is_outside_applications(&self, app_path: &Path) -> bool;
+
+    /// Like [`Installer::install`], but asks the system for administrator
+    /// privileges to copy the update into place.
+    async fn install_with_privileges(
+        &self,
+        asset_path: &Path,
+        app_path: &Path,
+        release_channel: ReleaseChannel,
+        verify_signature: bool,
+        report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()>;
+
+    /// Whether [`Installer::install`] only stages updates, because the
+    /// running app can't be replaced, for [`Installer::finish_install`] to
+    /// swap in once Zed has exited.
+    fn installs_on_restart(&self) -> bool;
+
+    /// Whether updates can be staged with [`Installer::stage`], so that they
+    /// can be installed as Zed quits.
+    fn can_stage(&self) -> bool;
+
+    /// Prepares the update asset at `asset_path` next to the app at
+    /// `app_path`, without touching the app, for
+    /// [`Installer::finish_install`] to swap in once Zed has exited.
+    async fn stage(
+        &self,
+        asset_path: &Path,
+        app_path: &Path,
+        release_channel: ReleaseChannel,
+        verify_signature: bool,
+        report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()>;
+
+    /// Arranges for an update staged for the app at `app_path` to be swapped
+    /// in once Zed has exited, relaunching it afterwards if `relaunch` is set.
+    /// Returns whether there was a staged update.
+    fn finish_install(&self, app_path: &Path, relaunch: bool) -> Result<bool>;
+
+    /// Quits Zed and relaunches it, so that an update installed over it is
+    /// used.
+    fn restart(&self, cx: &mut AppContext);
+
+    /// Checks that the app at `app_path` is the release that was just
+    /// installed over it.
+    async fn verify_installed_version(
+        &self,
+        app_path: &Path,
+        release_version: &str,
+        release_channel: ReleaseChannel,
+    ) -> Result<()>;
+
+    /// Keeps the version of the app that the last install replaced in
+    /// `cache_dir`, in place of any version kept there before. Returns
+    /// whether there was a version to keep.
+    async fn retain_previous_version(&self, app_path: &Path, cache_dir: &Path) -> Result<bool>;
+
+    /// Puts the version kept in `cache_dir` back in place of the app at
+    /// `app_path`.
+    async fn restore_previous_version(&self, app_path: &Path, cache_dir: &Path) -> Result<()>;
+}
+
+/// Installs disk images on macOS and tarballs on Linux, and stages zip
+/// archives on Windows.
+pub struct PlatformInstaller;
+
+#[async_trait]
+impl Installer for PlatformInstaller {
+    fn app_path(&self, cx: &AsyncAppContext) -> Result<PathBuf> {
+        ZED_APP_PATH
+            .clone()
+            .map_or_else(|| cx.update(|cx| cx.app_path())?, Ok)
+    }
+
+    async fn install(
+        &self,
+        asset_path: &Path,
+        app_path: &Path,
+        release_channel: ReleaseChannel,
+        verify_signature: bool,
+        report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()> {
+        #[cfg(target_os = "macos")]
+        {
+            let _ = release_channel;
+            install_dmg(asset_path, app_path, verify_signature, false, report_phase).await
+        }
+        // Extracting an archive is all there is to installing it.
+        #[cfg(target_os = "linux")]
+        {
+            let _ = (release_channel, verify_signature);
+            report_phase(InstallPhase::Copying);
+            if asset_path
+                .extension()
+                .map_or(false, |extension| extension == "dmg")
+            {
+                Err(anyhow::anyhow!(
+                    "disk images can only be installed on macOS"
+                ))?;
+            }
+            install_tarball(asset_path, app_path).await
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (release_channel, verify_signature);
+            report_phase(InstallPhase::Copying);
+            if asset_path
+                .extension()
+                .map_or(true, |extension| extension != "zip")
+            {
+                Err(anyhow::anyhow!(
+                    "only zip archives can be installed on Windows"
+                ))?;
+            }
+            install_zip(asset_path, app_path).await
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            let _ = (
+                asset_path,
+                app_path,
+                release_channel,
+                verify_signature,
+                report_phase,
+            );
+            Err(anyhow::anyhow!(
+                "auto-update is not supported on {}",
+                std::env::consts::OS
+            ))
+        }
+    }
+
+    fn requires_privileges(&self, app_path: &Path) -> bool {
+        #[cfg(target_os = "macos")]
+        {
+            requires_privileges_to_install(app_path)
+        }
+        // Tarballs are installed in the user's home directory.
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = app_path;
+            false
+        }
+    }
+
+    fn installed_version(&self, app_path: &Path) -> Option<SemanticVersion> {
+        #[cfg(target_os = "macos")]
+        {
+            installed_app_version(app_path)
+        }
+        // Tarballs and Windows archives don't record their version anywhere
+        // that's cheap to read.
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = app_path;
+            None
+        }
+    }
+
+    fn is_outside_applications(&self, app_path: &Path) -> bool {
+        #[cfg(target_os = "macos")]
+        {
+            is_outside_applications(app_path, &util::paths::HOME)
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = app_path;
+            false
+        }
+    }
+
+    async fn install_with_privileges(
+        &self,
+        asset_path: &Path,
+        app_path: &Path,
+        release_channel: ReleaseChannel,
+        verify_signature: bool,
+        report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()> {
+        #[cfg(target_os = "macos")]
+        {
+            let _ = release_channel;
+            install_dmg(asset_path, app_path, verify_signature, true, report_phase).await
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = (
+                asset_path,
+                app_path,
+                release_channel,
+                verify_signature,
+                report_phase,
+            );
+            Err(anyhow::anyhow!(
+                "installing with administrator privileges is only supported on macOS"
+            ))
+        }
+    }
+
+    fn installs_on_restart(&self) -> bool {
+        cfg!(target_os = "windows")
+    }
+
+    fn can_stage(&self) -> bool {
+        cfg!(any(target_os = "macos", target_os = "windows"))
+    }
+
+    async fn stage(
+        &self,
+        asset_path: &Path,
+        app_path: &Path,
+        release_channel: ReleaseChannel,
+        verify_signature: bool,
+        report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()> {
+        #[cfg(target_os = "macos")]
+        {
+            let _ = release_channel;
+            stage_dmg(asset_path, app_path, verify_signature, report_phase).await
+        }
+        // Installing on Windows already only stages the update.
+        #[cfg(target_os = "windows")]
+        {
+            self.install(
+                asset_path,
+                app_path,
+                release_channel,
+                verify_signature,
+                report_phase,
+            )
+            .await
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        {
+            let _ = (
+                asset_path,
+                app_path,
+                release_channel,
+                verify_signature,
+                report_phase,
+            );
+            Err(anyhow::anyhow!(
+                "staging updates is only supported on macOS and Windows"
+            ))
+        }
+    }
+
+    fn finish_install(&self, app_path: &Path, relaunch: bool) -> Result<bool> {
+        #[cfg(target_os = "macos")]
+        {
+            finish_staged_app(app_path, relaunch)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            finish_staged_install(app_path, relaunch)
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        {
+            let _ = (app_path, relaunch);
+            Ok(false)
+        }
+    }
+
+    fn restart(&self, cx: &mut AppContext) {
+        cx.restart();
+    }
+
+    async fn verify_installed_version(
+        &self,
+        app_path: &Path,
+        release_version: &str,
+        release_channel: ReleaseChannel,
+    ) -> Result<()> {
+        verify_installed_version(app_path, release_version, release_channel).await
+    }
+
+    async fn retain_previous_version(&self, app_path: &Path, cache_dir: &Path) -> Result<bool> {
+        #[cfg(target_os = "macos")]
+        {
+            retain_app_backup(app_path, cache_dir).await
+        }
+        // Tarball installs don't keep the version they replace.
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = app_path;
+            remove_dir_if_exists(cache_dir).await?;
+            Ok(false)
+        }
+    }
+
+    async fn restore_previous_version(&self, app_path: &Path, cache_dir: &Path) -> Result<()> {
+        restore_retained_app(app_path, cache_dir).await
+    }
+}
+
+/// The installer for the current platform.
+pub fn platform_installer() -> Arc<dyn Installer> {
+    Arc::new(PlatformInstaller)
+}
+
+/// The size of the trailer at the end of a UDIF disk image.
+#[cfg(any(test, target_os = "macos"))]
+const UDIF_TRAILER_LEN: usize = 512;
+
+/// Checks that a downloaded file looks like a disk image before handing it to
+/// `hdiutil`, so that a bad download isn't reported as a failure to mount.
+#[cfg(any(test, target_os = "macos"))]
+async fn check_disk_image(path: &Path) -> Result<()> {
+    use smol::io::{AsyncReadExt, AsyncSeekExt};
+    use std::io::SeekFrom;
+
+    let mut file = smol::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let mut head = vec![0; (len as usize).min(UDIF_TRAILER_LEN)];
+    file.read_exact(&mut head).await?;
+    let mut trailer = vec![0; (len as usize).min(UDIF_TRAILER_LEN)];
+    file.seek(SeekFrom::End(-(trailer.len() as i64))).await?;
+    file.read_exact(&mut trailer).await?;
+    validate_disk_image(&head, &trailer)?;
+    Ok(())
+}
+
+/// Checks the first and last bytes of a file for the `koly` trailer that UDIF
+/// disk images end with.
+#[cfg(any(test, target_os = "macos"))]
+fn validate_disk_image(head: &[u8], trailer: &[u8]) -> Result<(), UpdateError> {
+    if head.is_empty() {
+        return Err(UpdateError::NotADiskImage("file is empty".into()));
+    }
+    if trailer.len() == UDIF_TRAILER_LEN && trailer.starts_with(b"koly") {
+        return Ok(());
+    }
+    let is_text = head
+        .iter()
+        .all(|byte| byte.is_ascii_graphic() || byte.is_ascii_whitespace());
+    if is_text {
+        let start = head
+            .iter()
+            .skip_while(|byte| byte.is_ascii_whitespace())
+            .take_while(|byte| !byte.is_ascii_whitespace())
+            .take(16)
+            .map(|byte| *byte as char)
+            .collect::<String>();
+        Err(UpdateError::NotADiskImage(format!(
+            "starts with '{}'",
+            start
+        )))
+    } else {
+        Err(UpdateError::NotADiskImage("no koly trailer".into()))
+    }
+}
+
+/// Checks that an app bundle is validly signed and, unless `expected_team_id`
+/// is empty, that it was signed by that team.
+#[cfg(target_os = "macos")]
+async fn verify_code_signature(app_path: &Path, expected_team_id: &str) -> Result<()> {
+    let output = Command::new("codesign")
+        .args(&["--verify", "--deep", "--strict"])
+        .arg(app_path)
+        .output()
+        .await?;
+    if !output.status.success() {
+        Err(UpdateError::CodeSignature(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))?;
+    }
+    if expected_team_id.is_empty() {
+        return Ok(());
+    }
+
+    // `codesign` writes the signature's details to stderr.
+    let output = Command::new("codesign")
+        .arg("-dv")
+        .arg(app_path)
+        .output()
+        .await?;
+    let details = String::from_utf8_lossy(&output.stderr);
+    match parse_team_identifier(&details) {
+        Some(team_id) if team_id == expected_team_id => Ok(()),
+        team_id => Err(UpdateError::CodeSignature(format!(
+            "signed by team {}, expected {}",
+            team_id.unwrap_or("none"),
+            expected_team_id
+        )))?,
+    }
+}
+
+/// Finds the Team ID in the output of `codesign -dv`.
+#[cfg(any(test, target_os = "macos"))]
+fn parse_team_identifier(codesign_details: &str) -> Option<&str> {
+    codesign_details
+        .lines()
+        .find_map(|line| line.strip_prefix("TeamIdentifier="))
+        .map(str::trim)
+        .filter(|team_id| *team_id != "not set")
+}
+
+#[cfg(target_os = "macos")]
+async fn install_dmg(
+    dmg_path: &Path,
+    running_app_path: &Path,
+    verify_signature: bool,
+    privileged: bool,
+    report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+) -> Result<()> {
+    let temp_dir = super::create_update_temp_dir()?;
+
+    report_phase(InstallPhase::Mounting);
+    if let Err(error) = check_disk_image(dmg_path).await {
+        smol::fs::remove_file(dmg_path).await.log_err();
+        return Err(error);
+    }
+
+    // Dropped before `temp_dir`, so that the image is detached before the
+    // directory it's mounted in is removed.
+    let (mounted_image, mounted_app_path) = mount_disk_image(dmg_path, temp_dir.path()).await?;
+    log::info!(
+        "installing {:?} over {:?}",
+        mounted_app_path.file_name().unwrap_or_default(),
+        running_app_path
+    );
+
+    if verify_signature {
+        verify_code_signature(&mounted_app_path, EXPECTED_TEAM_ID).await?;
+    }
+
+    report_phase(InstallPhase::Copying);
+    if privileged {
+        copy_app_with_privileges(&mounted_app_path, running_app_path).await?;
+    } else if let Err(error) = swap_in_app(&mounted_app_path, running_app_path).await {
+        // Renaming a bundle that's running can misbehave on some filesystems,
+        // in which case the update is copied over it in place instead.
+        log::warn!(
+            "failed to swap in the update, copying it in place instead: {:?}",
+            error
+        );
+        // rsync stops at the first file it can't replace, which would leave
+        // a mix of both versions behind, so nothing is copied unless every
+        // file can be.
+        if let Some(path) = find_unwritable(running_app_path, &is_writable) {
+            Err(UpdateError::PermissionDenied(format!(
+                "{:?} isn't writable",
+                path
+            )))?;
+        }
+        rsync_app(&mounted_app_path, running_app_path).await?;
+    }
+
+    // Copying can drop metadata that the signature covers, which would stop
+    // the new version from launching.
+    if verify_signature {
+        if let Err(error) = verify_code_signature(running_app_path, EXPECTED_TEAM_ID).await {
+            restore_app_backup(running_app_path).await.log_err();
+            return Err(error);
+        }
+    }
+
+    // Files copied out of the disk image can keep its quarantine attribute,
+    // which makes Gatekeeper prompt, or translocate the app, on its next
+    // launch. The update is installed either way, so this isn't fatal.
+    remove_quarantine(running_app_path).await.log_err();
+
+    report_phase(InstallPhase::Unmounting);
+    mounted_image.detach().await
+}
+
+/// Finds the app bundle in a mounted disk image. Each channel's image holds a
+/// single bundle named for the channel, such as `Zed Preview.app`, which
+/// needn't match the name of the bundle it's installed over.
+#[cfg(any(test, target_os = "macos"))]
+async fn find_app_bundle(mount_path: &Path) -> Result<PathBuf> {
+    let mut app_paths = Vec::new();
+    let mut entries = smol::fs::read_dir(mount_path).await?;
+    while let Some(entry) = entries.next().await {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "app")
+            && smol::fs::metadata(&path)
+                .await
+                .map_or(false, |metadata| metadata.is_dir())
+        {
+            app_paths.push(path);
+        }
+    }
+    app_paths.sort();
+    match <[PathBuf; 1]>::try_from(app_paths) {
+        Ok([app_path]) => Ok(app_path),
+        Err(app_paths) if app_paths.is_empty() => Err(UpdateError::Mount(
+            "the update disk image doesn't contain an app".to_string(),
+        )
+        .into()),
+        Err(app_paths) => Err(UpdateError::Mount(format!(
+            "the update disk image contains more than one app: {:?}",
+            app_paths
+                .iter()
+                .filter_map(|app_path| app_path.file_name())
+                .collect::<Vec<_>>()
+        ))
+        .into()),
+    }
+}
+
+/// A mounted disk image. Unless it's detached with [`MountedImage::detach`],
+/// it's forcibly detached when dropped, so that an install that fails
+/// partway through doesn't leave it mounted.
+#[cfg(any(all(test, unix), target_os = "macos"))]
+struct MountedImage {
+    mount_path: PathBuf,
+    hdiutil: PathBuf,
+    detached: bool,
+}
+
+/// Mounts the disk image at `dmg_path` in `mount_root`, returning the
+/// mounted image and the app bundle it contains.
+#[cfg(target_os = "macos")]
+async fn mount_disk_image(dmg_path: &Path, mount_root: &Path) -> Result<(MountedImage, PathBuf)> {
+    let output = Command::new("hdiutil")
+        .args(&["attach", "-nobrowse"])
+        .arg(dmg_path)
+        .arg("-mountroot")
+        .arg(mount_root)
+        .output()
+        .await?;
+    if !output.status.success() {
+        Err(UpdateError::Mount(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))?;
+    }
+    let mount_path = mount_root.join("Zed");
+    let mounted_image = MountedImage::new(mount_path.clone());
+    let mounted_app_path = find_app_bundle(&mount_path).await?;
+    Ok((mounted_image, mounted_app_path))
+}
+
+/// Copies the app bundle in the disk image at `dmg_path` next to the one at
+/// `running_app_path`, without touching the running app, for
+/// [`finish_staged_app`] to swap in once Zed has exited. Replaces any update
+/// staged before.
+#[cfg(target_os = "macos")]
+async fn stage_dmg(
+    dmg_path: &Path,
+    running_app_path: &Path,
+    verify_signature: bool,
+    report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+) -> Result<()> {
+    let staged_app_path = sibling_app_path(running_app_path, "update")?;
+    remove_dir_if_exists(&staged_app_path).await?;
+    let temp_dir = super::create_update_temp_dir()?;
+
+    report_phase(InstallPhase::Mounting);
+    if let Err(error) = check_disk_image(dmg_path).await {
+        smol::fs::remove_file(dmg_path).await.log_err();
+        return Err(error);
+    }
+    let (mounted_image, mounted_app_path) = mount_disk_image(dmg_path, temp_dir.path()).await?;
+    if verify_signature {
+        verify_code_signature(&mounted_app_path, EXPECTED_TEAM_ID).await?;
+    }
+
+    report_phase(InstallPhase::Copying);
+    let output = copy_app_command(&mounted_app_path, &staged_app_path)
+        .output()
+        .await?;
+    if !output.status.success() {
+        remove_dir_if_exists(&staged_app_path).await.log_err();
+        Err(copy_error(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))?;
+    }
+    if verify_signature {
+        if let Err(error) = verify_code_signature(&staged_app_path, EXPECTED_TEAM_ID).await {
+            remove_dir_if_exists(&staged_app_path).await.log_err();
+            return Err(error);
+        }
+    }
+    remove_quarantine(&staged_app_path).await.log_err();
+
+    report_phase(InstallPhase::Unmounting);
+    mounted_image.detach().await
+}
+
+/// Swaps in the update that [`stage_dmg`] staged next to the app at
+/// `running_app_path`, if there is one, then relaunches the app if
+/// `relaunch` is set. Only renames are needed, so this is quick enough to
+/// do as Zed quits. Returns whether there was a staged update.
+#[cfg(target_os = "macos")]
+fn finish_staged_app(running_app_path: &Path, relaunch: bool) -> Result<bool> {
+    let staged_app_path = sibling_app_path(running_app_path, "update")?;
+    if !staged_app_path.is_dir() {
+        return Ok(false);
+    }
+    let backup_app_path = app_backup_path(running_app_path)?;
+    if backup_app_path.exists() {
+        std::fs::remove_dir_all(&backup_app_path)?;
+    }
+    std::fs::rename(running_app_path, &backup_app_path)
+        .map_err(|error| copy_error(error.to_string()))?;
+    if let Err(error) = std::fs::rename(&staged_app_path, running_app_path) {
+        std::fs::rename(&backup_app_path, running_app_path).log_err();
+        Err(copy_error(error.to_string()))?;
+    }
+    // Removing the previous version can take a while, so it's left to a
+    // process that outlives Zed.
+    std::process::Command::new("rm")
+        .arg("-rf")
+        .arg(&backup_app_path)
+        .spawn()
+        .log_err();
+    if relaunch {
+        self::relaunch(running_app_path)?;
+    }
+    Ok(true)
+}
+
+#[cfg(any(all(test, unix), target_os = "macos"))]
+impl MountedImage {
+    #[cfg(target_os = "macos")]
+    fn new(mount_path: PathBuf) -> Self {
+        Self {
+            mount_path,
+            hdiutil: "hdiutil".into(),
+            detached: false,
+        }
+    }
+
+    async fn detach(mut self) -> Result<()> {
+        let output = Command::new(&self.hdiutil)
+            .arg("detach")
+            .arg(&self.mount_path)
+            .output()
+            .await?;
+        if !output.status.success() {
+            Err(UpdateError::Unmount(
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ))?;
+        }
+        self.detached = true;
+        Ok(())
+    }
+}
+
+#[cfg(any(all(test, unix), target_os = "macos"))]
+impl Drop for MountedImage {
+    fn drop(&mut self) {
+        if self.detached {
+            return;
+        }
+        // Blocks, but only when an install has already failed.
+        let output = std::process::Command::new(&self.hdiutil)
+            .args(["detach", "-force"])
+            .arg(&self.mount_path)
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => log::error!(
+                "failed to detach {:?}: {}",
+                self.mount_path,
+                String::from_utf8_lossy(&output.stderr)
+            ),
+            Err(error) => log::error!("failed to detach {:?}: {}", self.mount_path, error),
+        }
+    }
+}
+
+/// The command that copies the bundle at `new_app_path` to `dest_path`,
+/// which mustn't exist yet. On macOS this is `ditto`, which, unlike the old
+/// `rsync` that ships with it, keeps the extended attributes and resource
+/// forks that the bundle's code signature covers.
+fn copy_app_command(new_app_path: &Path, dest_path: &Path) -> Command {
+    let mut command;
+    if cfg!(target_os = "macos") {
+        command = Command::new("ditto");
+        command.arg("--rsrc");
+    } else {
+        command = Command::new("cp");
+        command.arg("-pR");
+    }
+    command.arg(new_app_path).arg(dest_path);
+    command
+}
+
+/// Copies the app bundle at `new_app_path` next to the one at `app_path`, then
+/// swaps it in with renames, so that an interrupted install doesn't leave a
+/// bundle that's part old and part new. The previous bundle is kept at its
+/// [`app_backup_path`] until the install has been verified.
+async fn swap_in_app(new_app_path: &Path, app_path: &Path) -> Result<()> {
+    let staged_app_path = staged_app_path(app_path)?;
+    let backup_app_path = app_backup_path(app_path)?;
+    remove_dir_if_exists(&staged_app_path).await?;
+    remove_dir_if_exists(&backup_app_path).await?;
+
+    let output = copy_app_command(new_app_path, &staged_app_path)
+        .output()
+        .await?;
+    if !output.status.success() {
+        remove_dir_if_exists(&staged_app_path).await.log_err();
+        Err(copy_error(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))?;
+    }
+
+    if let Err(error) = smol::fs::rename(app_path, &backup_app_path).await {
+        remove_dir_if_exists(&staged_app_path).await.log_err();
+        Err(copy_error(error.to_string()))?;
+    }
+    if let Err(error) = smol::fs::rename(&staged_app_path, app_path).await {
+        smol::fs::rename(&backup_app_path, app_path).await.log_err();
+        remove_dir_if_exists(&staged_app_path).await.log_err();
+        Err(copy_error(error.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Copies the app bundle at `new_app_path` over the one at `app_path`.
+#[cfg(target_os = "macos")]
+async fn rsync_app(new_app_path: &Path, app_path: &Path) -> Result<()> {
+    use std::ffi::OsString;
+
+    // The trailing slash makes rsync copy the bundle's contents, rather than
+    // nesting the bundle inside the running one.
+    let mut rsync_source: OsString = new_app_path.into();
+    rsync_source.push("/");
+    let output = Command::new("rsync")
+        .args(&["-av", "--delete"])
+        .arg(&rsync_source)
+        .arg(app_path)
+        .output()
+        .await?;
+    if !output.status.success() {
+        Err(copy_error(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))?;
+    }
+    Ok(())
+}
+
+/// Copies the app bundle at `new_app_path` over the one at `app_path` as an
+/// administrator, after the system asks the user for their password.
+#[cfg(target_os = "macos")]
+async fn copy_app_with_privileges(new_app_path: &Path, app_path: &Path) -> Result<()> {
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(privileged_copy_script(new_app_path, app_path))
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // AppleScript's error -128 means that the user cancelled the prompt.
+        if stderr.contains("(-128)") {
+            Err(UpdateError::Cancelled)?;
+        }
+        Err(UpdateError::Copy(stderr.into_owned()))?;
+    }
+    Ok(())
+}
+
+/// The AppleScript that copies the app bundle at `new_app_path` over the one
+/// at `app_path` with administrator privileges.
+#[cfg(any(test, target_os = "macos"))]
+fn privileged_copy_script(new_app_path: &Path, app_path: &Path) -> String {
+    // The trailing slash makes rsync copy the bundle's contents, as in
+    // `rsync_app`.
+    let command = format!(
+        "/usr/bin/rsync -a --delete {} {}",
+        shell_quote(&format!("{}/", new_app_path.display())),
+        shell_quote(&app_path.display().to_string()),
+    );
+    format!(
+        "do shell script \"{}\" with administrator privileges",
+        command.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+#[cfg(any(test, target_os = "macos"))]
+fn shell_quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', r"'\''"))
+}
+
+/// Classifies a failure to copy the update into place, so that one caused by
+/// missing permissions can be retried with administrator privileges.
+fn copy_error(message: String) -> UpdateError {
+    if message.contains("Permission denied") || message.contains("Operation not permitted") {
+        UpdateError::PermissionDenied(message)
+    } else {
+        UpdateError::Copy(message)
+    }
+}
+
+/// Whether installing over the app at `app_path` needs administrator
+/// privileges: an update is swapped in by renaming it next to the app, or
+/// failing that copied over it, so either needs to be writable.
+#[cfg(target_os = "macos")]
+fn requires_privileges_to_install(app_path: &Path) -> bool {
+    !is_writable(app_path) && !app_path.parent().map_or(false, is_writable)
+}
+
+/// Finds a file or directory in the app bundle at `app_path`, or the bundle
+/// itself, that `is_writable` says can't be written to. Symlinks are
+/// replaced rather than written through, so they aren't followed.
+#[cfg(any(test, target_os = "macos"))]
+fn find_unwritable(app_path: &Path, is_writable: &dyn Fn(&Path) -> bool) -> Option<PathBuf> {
+    if !is_writable(app_path) {
+        return Some(app_path.to_path_buf());
+    }
+    let is_dir = std::fs::symlink_metadata(app_path).map_or(false, |metadata| metadata.is_dir());
+    if !is_dir {
+        return None;
+    }
+    std::fs::read_dir(app_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .find_map(|entry| find_unwritable(&entry.path(), is_writable))
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn is_writable(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is a valid NUL-terminated string for the duration of
+    // the call.
+    unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 }
+}
+
+/// Whether the app at `app_path` is outside both the system's and the user's
+/// Applications folders, such as in `~/Downloads`.
+#[cfg(any(test, target_os = "macos"))]
+fn is_outside_applications(app_path: &Path, home_dir: &Path) -> bool {
+    !app_path.starts_with("/Applications") && !app_path.starts_with(home_dir.join("Applications"))
+}
+
+/// Copies the app bundle at `app_path` into the Applications folder, returning
+/// where it was copied to. The copy's code signature is checked if
+/// `verify_signature` is set, and any copy it replaced is put back if that
+/// fails.
+#[cfg(target_os = "macos")]
+pub(crate) async fn copy_to_applications(
+    app_path: &Path,
+    verify_signature: bool,
+) -> Result<PathBuf> {
+    let app_filename = app_path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid running app path"))?;
+    let installed_app_path = Path::new("/Applications").join(app_filename);
+    let replaced = installed_app_path.exists();
+    if replaced {
+        swap_in_app(app_path, &installed_app_path).await?;
+    } else {
+        let output = copy_app_command(app_path, &installed_app_path)
+            .output()
+            .await?;
+        if !output.status.success() {
+            remove_dir_if_exists(&installed_app_path).await.log_err();
+            Err(copy_error(
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ))?;
+        }
+    }
+    // Otherwise macOS would translocate the copy too.
+    remove_quarantine(&installed_app_path).await.log_err();
+
+    if verify_signature {
+        if let Err(error) = verify_code_signature(&installed_app_path, EXPECTED_TEAM_ID).await {
+            if replaced {
+                restore_app_backup(&installed_app_path).await.log_err();
+            } else {
+                remove_dir_if_exists(&installed_app_path).await.log_err();
+            }
+            return Err(error);
+        }
+    }
+    if replaced {
+        remove_dir_if_exists(&app_backup_path(&installed_app_path)?)
+            .await
+            .log_err();
+    }
+    Ok(installed_app_path)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) async fn copy_to_applications(_: &Path, _: bool) -> Result<PathBuf> {
+    Err(anyhow!(
+        "moving Zed to Applications is only supported on macOS"
+    ))
+}
+
+/// Opens the app at `app_path` once this process has exited.
+#[cfg(unix)]
+pub(crate) fn relaunch(app_path: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt as _;
+
+    let script = r#"
+        while kill -0 $0 2> /dev/null; do
+            sleep 0.1
+        done
+        open "$1"
+    "#;
+    std::process::Command::new("/bin/bash")
+        .arg("-c")
+        .arg(script)
+        .arg(std::process::id().to_string())
+        .arg(app_path)
+        .process_group(0)
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn relaunch(_: &Path) -> Result<()> {
+    Err(anyhow!("relaunching is not supported on this platform"))
+}
+
+/// Where an update is copied to before it's swapped in, on the same volume
+/// as the app so that it can be renamed into place.
+fn staged_app_path(app_path: &Path) -> Result<PathBuf> {
+    sibling_app_path(app_path, &format!("update-{}", std::process::id()))
+}
+
+/// Where the previous version of the app is kept while an update is swapped
+/// in.
+fn app_backup_path(app_path: &Path) -> Result<PathBuf> {
+    sibling_app_path(app_path, "backup")
+}
+
+fn sibling_app_path(app_path: &Path, suffix: &str) -> Result<PathBuf> {
+    let mut file_name = app_path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid app path {:?}", app_path))?
+        .to_os_string();
+    file_name.push(".");
+    file_name.push(suffix);
+    Ok(app_path.with_file_name(file_name))
+}
+
+/// Puts the previous version of the app back, if a swap left it behind.
+#[cfg(any(test, target_os = "macos"))]
+async fn restore_app_backup(app_path: &Path) -> Result<()> {
+    let backup_app_path = app_backup_path(app_path)?;
+    if smol::fs::metadata(&backup_app_path).await.is_err() {
+        return Ok(());
+    }
+    remove_dir_if_exists(app_path).await?;
+    smol::fs::rename(&backup_app_path, app_path).await?;
+    Ok(())
+}
+
+/// Moves the previous version of the app, left behind by swapping in an
+/// update, into `cache_dir`, replacing any version kept there before.
+/// Returns whether there was a previous version to keep.
+#[cfg(any(test, target_os = "macos"))]
+async fn retain_app_backup(app_path: &Path, cache_dir: &Path) -> Result<bool> {
+    let backup_app_path = app_backup_path(app_path)?;
+    remove_dir_if_exists(cache_dir).await?;
+    if smol::fs::metadata(&backup_app_path).await.is_err() {
+        return Ok(false);
+    }
+
+    smol::fs::create_dir_all(cache_dir).await?;
+    let retained_app_path = retained_app_path(app_path, cache_dir)?;
+    if let Err(error) = smol::fs::rename(&backup_app_path, &retained_app_path).await {
+        remove_dir_if_exists(&backup_app_path).await.log_err();
+        Err(error)?;
+    }
+    Ok(true)
+}
+
+/// Swaps the version of the app kept in `cache_dir` back into place.
+async fn restore_retained_app(app_path: &Path, cache_dir: &Path) -> Result<()> {
+    let retained_app_path = retained_app_path(app_path, cache_dir)?;
+    if smol::fs::metadata(&retained_app_path).await.is_err() {
+        Err(anyhow!("no previous version has been kept"))?;
+    }
+    swap_in_app(&retained_app_path, app_path).await?;
+    remove_dir_if_exists(&app_backup_path(app_path)?)
+        .await
+        .log_err();
+    remove_dir_if_exists(cache_dir).await.log_err();
+    Ok(())
+}
+
+fn retained_app_path(app_path: &Path, cache_dir: &Path) -> Result<PathBuf> {
+    let file_name = app_path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid app path {:?}", app_path))?;
+    Ok(cache_dir.join(file_name))
+}
+
+#[cfg(target_os = "macos")]
+const QUARANTINE_ATTRIBUTE: &str = "com.apple.quarantine";
+
+/// Recursively removes the quarantine attribute from `path`, checking that
+/// no file is left with it.
+#[cfg(target_os = "macos")]
+async fn remove_quarantine(path: &Path) -> Result<()> {
+    // `xattr` fails if any file lacks the attribute, so its status is ignored
+    // in favor of checking what's left.
+    Command::new("xattr")
+        .args(&["-dr", QUARANTINE_ATTRIBUTE])
+        .arg(path)
+        .output()
+        .await?;
+
+    let output = Command::new("find")
+        .arg(path)
+        .args(&["-xattrname", QUARANTINE_ATTRIBUTE])
+        .output()
+        .await?;
+    if !output.status.success() {
+        Err(anyhow!(
+            "failed to look for quarantined files: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        ))?;
+    }
+    let quarantined = String::from_utf8_lossy(&output.stdout);
+    let quarantined = quarantined.lines().collect::<Vec<_>>();
+    if !quarantined.is_empty() {
+        Err(anyhow!(
+            "{} files are still quarantined, including {:?}",
+            quarantined.len(),
+            quarantined[0]
+        ))?;
+    }
+    Ok(())
+}
+
+/// The parts of an app bundle's `Info.plist` that identify its version.
+#[derive(Debug, serde::Deserialize)]
+#[cfg(any(test, target_os = "macos"))]
+struct InfoPlist {
+    #[serde(rename = "CFBundleShortVersionString")]
+    short_version: String,
+    /// The commit the bundle was built from, added by `script/bundle-mac`.
+    #[serde(rename = "ZedCommitSHA", default)]
+    commit_sha: Option<String>,
+}
+
+/// The version of the app bundle at `app_path`, according to its
+/// `Info.plist`.
+#[cfg(target_os = "macos")]
+fn installed_app_version(app_path: &Path) -> Option<SemanticVersion> {
+    plist::from_file::<_, InfoPlist>(app_path.join("Contents/Info.plist"))
+        .ok()?
+        .short_version
+        .parse()
+        .ok()
+}
+
+/// Checks that the app bundle at `running_app_path` is the version that was
+/// just installed over it.
+#[cfg(target_os = "macos")]
+async fn verify_installed_version(
+    running_app_path: &Path,
+    release_version: &str,
+    release_channel: ReleaseChannel,
+) -> Result<()> {
+    let plist_path = running_app_path.join("Contents/Info.plist");
+    let result = plist::from_file::<_, InfoPlist>(&plist_path)
+        .map_err(|error| {
+            UpdateError::InstallVerification(format!("failed to read {:?}: {}", plist_path, error))
+        })
+        .and_then(|plist| check_installed_version(&plist, release_version, release_channel));
+
+    // Once the new version is known to be in place, the previous one is
+    // kept for rolling back by `retain_app_backup`.
+    if result.is_err() {
+        restore_app_backup(running_app_path).await.log_err();
+    }
+    Ok(result?)
+}
+
+/// Tarballs and Windows archives are swapped into place with a rename, so
+/// they are either installed in full or not at all.
+#[cfg(not(target_os = "macos"))]
+async fn verify_installed_version(
+    _running_app_path: &Path,
+    _release_version: &str,
+    _release_channel: ReleaseChannel,
+) -> Result<()> {
+    Ok(())
+}
+
+/// Compares the version in an installed bundle's `Info.plist` against the
+/// release that was installed. Nightly releases are identified by commit,
+/// which bundles built before it was recorded in the plist don't have.
+#[cfg(any(test, target_os = "macos"))]
+fn check_installed_version(
+    plist: &InfoPlist,
+    release_version: &str,
+    release_channel: ReleaseChannel,
+) -> Result<(), UpdateError> {
+    if release_channel == ReleaseChannel::Nightly {
+        return match plist.commit_sha.as_deref() {
+            Some(commit_sha) if commit_sha != release_version => {
+                Err(UpdateError::InstallVerification(format!(
+                    "found commit {}, expected {}",
+                    commit_sha, release_version
+                )))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    let installed = plist.short_version.parse::<SemanticVersion>().ok();
+    let expected = release_version.parse::<SemanticVersion>().ok();
+    if installed.is_none() || installed != expected {
+        Err(UpdateError::InstallVerification(format!(
+            "found version {}, expected {}",
+            plist.short_version, release_version
+        )))?;
+    }
+    Ok(())
+}
+
+/// Installs a release tarball over the installation containing the running
+/// binary. The tarball is extracted next to the existing installation so
+/// that it can be swapped in with a rename.
+///
+/// Only a `zed.app` directory containing the running binary is replaced, and
+/// only by one containing the binary at the same path, so that a binary
+/// copied somewhere like `~/.local/bin` never takes its surroundings with it.
+#[cfg(target_os = "linux")]
+async fn install_tarball(tarball_path: &Path, running_app_path: &Path) -> Result<()> {
+    use crate::CannotAutoUpdateReason;
+
+    let unrecognized_installation =
+        || UpdateError::CannotAutoUpdate(CannotAutoUpdateReason::UnrecognizedInstallation);
+    let install_dir = running_app_path
+        .parent()
+        .and_then(|bin_dir| bin_dir.parent())
+        .filter(|install_dir| {
+            install_dir
+                .extension()
+                .map_or(false, |extension| extension == "app")
+        })
+        .ok_or_else(unrecognized_installation)?;
+    let executable_path = running_app_path.strip_prefix(install_dir)?;
+    if !install_dir.join(executable_path).is_file() {
+        Err(unrecognized_installation())?;
+    }
+    let install_parent_dir = install_dir
+        .parent()
+        .ok_or_else(|| anyhow!("invalid running app path"))?;
+    // Such as when Zed was installed system-wide by a package manager, which
+    // should be what updates it.
+    if !is_writable(install_parent_dir) {
+        Err(UpdateError::Copy(format!(
+            "{:?} isn't writable by the current user. Update Zed with the package manager \
+            that installed it, or reinstall it somewhere you own, such as ~/.local",
+            install_parent_dir
+        )))?;
+    }
+    let staging_dir = tempfile::Builder::new()
+        .prefix(".zed-auto-update")
+        .tempdir_in(install_parent_dir)?;
+
+    let output = Command::new("tar")
+        .arg("-xzf")
+        .arg(tarball_path)
+        .arg("-C")
+        .arg(staging_dir.path())
+        .output()
+        .await?;
+    if !output.status.success() {
+        Err(anyhow!(
+            "failed to extract: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        ))?;
+    }
+
+    let extracted_dir = extracted_install_dir(staging_dir.path()).await?;
+    if !extracted_dir.join(executable_path).is_file() {
+        Err(unrecognized_installation())?;
+    }
+    let previous_install_dir = staging_dir.path().join("previous");
+    smol::fs::rename(install_dir, &previous_install_dir).await?;
+    if let Err(error) = smol::fs::rename(&extracted_dir, install_dir).await {
+        smol::fs::rename(&previous_install_dir, install_dir)
+            .await
+            .log_err();
+        Err(UpdateError::Copy(error.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Extracts a release zip archive next to the installation containing the
+/// running executable. Windows won't replace an executable while it's
+/// running, so the extracted installation is swapped in by
+/// [`finish_staged_install`] once Zed has exited.
+#[cfg(target_os = "windows")]
+async fn install_zip(zip_path: &Path, running_app_path: &Path) -> Result<()> {
+    let install_dir = running_app_path
+        .parent()
+        .ok_or_else(|| anyhow!("invalid running app path"))?;
+    let install_parent_dir = install_dir
+        .parent()
+        .ok_or_else(|| anyhow!("invalid running app path"))?;
+    let staged_install_dir = sibling_app_path(install_dir, "update")?;
+    remove_dir_if_exists(&staged_install_dir).await?;
+    let staging_dir = tempfile::Builder::new()
+        .prefix(".zed-auto-update")
+        .tempdir_in(install_parent_dir)?;
+
+    // The tar that ships with Windows extracts zip archives too.
+    let output = Command::new("tar")
+        .arg("-xf")
+        .arg(zip_path)
+        .arg("-C")
+        .arg(staging_dir.path())
+        .output()
+        .await?;
+    if !output.status.success() {
+        Err(anyhow!(
+            "failed to extract: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        ))?;
+    }
+
+    let extracted_dir = extracted_install_dir(staging_dir.path()).await?;
+    let executable_name = running_app_path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid running app path"))?;
+    if smol::fs::metadata(extracted_dir.join(executable_name))
+        .await
+        .is_err()
+    {
+        Err(anyhow!(
+            "the update archive doesn't contain {:?}",
+            executable_name
+        ))?;
+    }
+    smol::fs::rename(&extracted_dir, &staged_install_dir).await?;
+    Ok(())
+}
+
+/// Starts a helper that waits for Zed to exit, then swaps the installation
+/// staged by [`install_zip`] in for the one containing `running_app_path`,
+/// relaunching Zed afterwards if `relaunch` is set. Returns whether there was
+/// a staged installation.
+#[cfg(target_os = "windows")]
+fn finish_staged_install(running_app_path: &Path, relaunch: bool) -> Result<bool> {
+    use std::os::windows::process::CommandExt as _;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let install_dir = running_app_path
+        .parent()
+        .ok_or_else(|| anyhow!("invalid running app path"))?;
+    let staged_install_dir = sibling_app_path(install_dir, "update")?;
+    if !staged_install_dir.is_dir() {
+        return Ok(false);
+    }
+    let script = finish_install_script(
+        std::process::id(),
+        install_dir,
+        &staged_install_dir,
+        &sibling_app_path(install_dir, "backup")?,
+        relaunch.then_some(running_app_path),
+    );
+    std::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()?;
+    Ok(true)
+}
+
+/// The PowerShell script that waits for the process `pid` to exit, then
+/// swaps `staged_install_dir` in for `install_dir`, putting the previous
+/// installation back if that fails. Zed is launched from `relaunch`, if
+/// given, whether or not the swap succeeded.
+#[cfg(any(test, target_os = "windows"))]
+fn finish_install_script(
+    pid: u32,
+    install_dir: &Path,
+    staged_install_dir: &Path,
+    backup_dir: &Path,
+    relaunch: Option<&Path>,
+) -> String {
+    let install_dir = powershell_quote(&install_dir.to_string_lossy());
+    let staged_install_dir = powershell_quote(&staged_install_dir.to_string_lossy());
+    let backup_dir = powershell_quote(&backup_dir.to_string_lossy());
+    let relaunch = relaunch.map_or(String::new(), |app_path| {
+        format!(
+            "Start-Process -FilePath {}",
+            powershell_quote(&app_path.to_string_lossy())
+        )
+    });
+    format!(
+        r#"$ErrorActionPreference = 'Stop'
+while (Get-Process -Id {pid} -ErrorAction SilentlyContinue) {{
+    Start-Sleep -Milliseconds 100
+}}
+try {{
+    if (Test-Path -LiteralPath {backup_dir}) {{
+        Remove-Item -LiteralPath {backup_dir} -Recurse -Force
+    }}
+    # Files can stay locked for a moment after the process exits.
+    for ($attempt = 1; ; $attempt++) {{
+        try {{
+            Move-Item -LiteralPath {install_dir} -Destination {backup_dir}
+            break
+        }} catch {{
+            if ($attempt -ge 50) {{ throw }}
+            Start-Sleep -Milliseconds 100
+        }}
+    }}
+    try {{
+        Move-Item -LiteralPath {staged_install_dir} -Destination {install_dir}
+    }} catch {{
+        Move-Item -LiteralPath {backup_dir} -Destination {install_dir}
+        throw
+    }}
+    Remove-Item -LiteralPath {backup_dir} -Recurse -Force -ErrorAction SilentlyContinue
+}} finally {{
+    {relaunch}
+}}
+"#
+    )
+}
+
+/// Quotes `argument` as a single-quoted PowerShell string, in which only
+/// single quotes need escaping.
+#[cfg(any(test, target_os = "windows"))]
+fn powershell_quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', "''"))
+}
+
+/// The directory holding the whole installation, which is the only directory
+/// in a release archive.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+async fn extracted_install_dir(staging_dir: &Path) -> Result<PathBuf> {
+    let mut extracted_dirs = Vec::new();
+    let mut entries = smol::fs::read_dir(staging_dir).await?;
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        if entry.file_type().await?.is_dir() {
+            extracted_dirs.push(entry.path());
+        }
+    }
+    match <[PathBuf; 1]>::try_from(extracted_dirs) {
+        Ok([extracted_dir]) => Ok(extracted_dir),
+        Err(extracted_dirs) => Err(anyhow!(
+            "expected a single directory in the update archive, found {}",
+            extracted_dirs.len()
+        )),
+    }
+}
+
+/// Pretends to install updates over an app at the given path, without
+/// touching it.
+#[cfg(test)]
+#[derive(Default)]
+pub struct NoopInstaller {
+    pub app_path: PathBuf,
+    pub requires_privileges: bool,
+    pub installs_on_restart: bool,
+    pub installed_version: Option<SemanticVersion>,
+    /// How many times Zed would have been restarted.
+    pub restarts: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Installer for NoopInstaller {
+    fn app_path(&self, _: &AsyncAppContext) -> Result<PathBuf> {
+        Ok(self.app_path.clone())
+    }
+
+    async fn install(
+        &self,
+        _: &Path,
+        _: &Path,
+        _: ReleaseChannel,
+        _: bool,
+        report_phase: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()> {
+        report_phase(InstallPhase::Mounting);
+        report_phase(InstallPhase::Copying);
+        report_phase(InstallPhase::Unmounting);
+        Ok(())
+    }
+
+    fn requires_privileges(&self, _: &Path) -> bool {
+        self.requires_privileges
+    }
+
+    fn installed_version(&self, _: &Path) -> Option<SemanticVersion> {
+        self.installed_version
+    }
+
+    fn is_outside_applications(&self, _: &Path) -> bool {
+        false
+    }
+
+    async fn install_with_privileges(
+        &self,
+        _: &Path,
+        _: &Path,
+        _: ReleaseChannel,
+        _: bool,
+        _: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn installs_on_restart(&self) -> bool {
+        self.installs_on_restart
+    }
+
+    fn can_stage(&self) -> bool {
+        true
+    }
+
+    async fn stage(
+        &self,
+        _: &Path,
+        _: &Path,
+        _: ReleaseChannel,
+        _: bool,
+        _: &(dyn Fn(InstallPhase) + Send + Sync),
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn finish_install(&self, _: &Path, _: bool) -> Result<bool> {
+        Ok(self.installs_on_restart)
+    }
+
+    fn restart(&self, _: &mut AppContext) {
+        self.restarts
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    async fn verify_installed_version(&self, _: &Path, _: &str, _: ReleaseChannel) -> Result<()> {
+        Ok(())
+    }
+
+    async fn retain_previous_version(&self, _: &Path, _: &Path) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn restore_previous_version(&self, _: &Path, _: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_disk_image() {
+        let mut dmg = vec![0x78, 0xda, 0x63, 0x60];
+        dmg.resize(4096, 0);
+        let mut trailer = vec![0; UDIF_TRAILER_LEN];
+        trailer[..4].copy_from_slice(b"koly");
+        dmg.extend_from_slice(&trailer);
+        let head = &dmg[..UDIF_TRAILER_LEN];
+        let tail = &dmg[dmg.len() - UDIF_TRAILER_LEN..];
+        assert_eq!(validate_disk_image(head, tail), Ok(()));
+
+        let html = b"<!DOCTYPE html>\n<html><body>Sign in to Wi-Fi</body></html>";
+        assert_eq!(
+            validate_disk_image(html, html),
+            Err(UpdateError::NotADiskImage("starts with '<!DOCTYPE'".into()))
+        );
+        assert_eq!(
+            UpdateError::NotADiskImage("starts with '<!DOCTYPE'".into()).to_string(),
+            "downloaded file does not look like a disk image (starts with '<!DOCTYPE')"
+        );
+
+        let truncated = &dmg[..dmg.len() - 100];
+        assert_eq!(
+            validate_disk_image(head, &truncated[truncated.len() - UDIF_TRAILER_LEN..]),
+            Err(UpdateError::NotADiskImage("no koly trailer".into()))
+        );
+
+        assert_eq!(
+            validate_disk_image(&[], &[]),
+            Err(UpdateError::NotADiskImage("file is empty".into()))
+        );
+    }
+
+    #[gpui::test]
+    async fn test_check_disk_image() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("Zed.dmg");
+        let mut dmg = vec![0xff; 1024];
+        dmg.extend_from_slice(b"koly");
+        dmg.resize(1024 + UDIF_TRAILER_LEN, 0);
+        std::fs::write(&path, &dmg).unwrap();
+        check_disk_image(&path).await.unwrap();
+
+        std::fs::write(&path, "").unwrap();
+        assert!(check_disk_image(&path).await.is_err());
+    }
+
+    #[test]
+    fn test_check_installed_version() {
+        let plist = |short_version: &str, commit_sha: Option<&str>| InfoPlist {
+            short_version: short_version.into(),
+            commit_sha: commit_sha.map(Into::into),
+        };
+
+        assert_eq!(
+            check_installed_version(&plist("0.122.0", None), "0.122.0", ReleaseChannel::Stable),
+            Ok(())
+        );
+        assert_eq!(
+            check_installed_version(&plist("0.121.5", None), "0.122.0", ReleaseChannel::Stable),
+            Err(UpdateError::InstallVerification(
+                "found version 0.121.5, expected 0.122.0".into()
+            ))
+        );
+        assert!(check_installed_version(
+            &plist("garbage", None),
+            "0.122.0",
+            ReleaseChannel::Preview
+        )
+        .is_err());
+
+        // Nightly builds share a version number, so their commits are compared.
+        assert_eq!(
+            check_installed_version(
+                &plist("0.122.0", Some("abc123")),
+                "abc123",
+                ReleaseChannel::Nightly
+            ),
+            Ok(())
+        );
+        assert!(check_installed_version(
+            &plist("0.122.0", Some("def456")),
+            "abc123",
+            ReleaseChannel::Nightly
+        )
+        .is_err());
+        assert_eq!(
+            check_installed_version(&plist("0.122.0", None), "abc123", ReleaseChannel::Nightly),
+            Ok(())
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[gpui::test]
+    async fn test_remove_quarantine() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let app_path = temp_dir.path().join("Zed.app");
+        let executable_dir = app_path.join("Contents/MacOS");
+        std::fs::create_dir_all(&executable_dir).unwrap();
+        std::fs::write(executable_dir.join("zed"), "").unwrap();
+        std::fs::write(app_path.join("Contents/Info.plist"), "").unwrap();
+        let status = std::process::Command::new("xattr")
+            .args(["-w", QUARANTINE_ATTRIBUTE, "0081;00000000;Zed;"])
+            .arg(executable_dir.join("zed"))
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        remove_quarantine(&app_path).await.unwrap();
+        let output = std::process::Command::new("xattr")
+            .arg("-lr")
+            .arg(&app_path)
+            .output()
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&output.stdout).contains(QUARANTINE_ATTRIBUTE));
+    }
+
+    #[cfg(unix)]
+    #[gpui::test]
+    async fn test_mounted_image_is_detached() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stands in for `hdiutil`, recording how it was called.
+        let temp_dir = tempfile::tempdir().unwrap();
+        let log_path = temp_dir.path().join("hdiutil.log");
+        let hdiutil = temp_dir.path().join("hdiutil");
+        std::fs::write(
+            &hdiutil,
+            format!("#!/bin/sh\necho \"$@\" >> {:?}\n", log_path),
+        )
+        .unwrap();
+        std::fs::set_permissions(&hdiutil, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mount = |mount_path: &str| MountedImage {
+            mount_path: mount_path.into(),
+            hdiutil: hdiutil.clone(),
+            detached: false,
+        };
+        let log = || std::fs::read_to_string(&log_path).unwrap_or_default();
+
+        mount("/tmp/ok").detach().await.unwrap();
+        assert_eq!(log(), "detach /tmp/ok\n");
+
+        // An install that fails between mounting and unmounting.
+        let install = |image: MountedImage| async move {
+            let _image = image;
+            Err::<(), _>(UpdateError::Copy("disk full".into()))
+        };
+        assert!(install(mount("/tmp/failed")).await.is_err());
+        assert_eq!(log(), "detach /tmp/ok\ndetach -force /tmp/failed\n");
+    }
+
+    #[gpui::test]
+    async fn test_find_app_bundle() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mount_path = |name: &str, entries: &[&str]| {
+            let mount_path = temp_dir.path().join(name);
+            std::fs::create_dir_all(&mount_path).unwrap();
+            for entry in entries {
+                if let Some(dir) = entry.strip_suffix('/') {
+                    std::fs::create_dir_all(mount_path.join(dir).join("Contents")).unwrap();
+                } else {
+                    std::fs::write(mount_path.join(entry), "").unwrap();
+                }
+            }
+            mount_path
+        };
+
+        // The bundle is found whatever it's called, alongside the usual
+        // contents of a disk image.
+        let preview = mount_path(
+            "preview",
+            &[
+                "Zed Preview.app/",
+                "Applications/",
+                ".background/",
+                "notes.app",
+            ],
+        );
+        assert_eq!(
+            find_app_bundle(&preview).await.unwrap(),
+            preview.join("Zed Preview.app")
+        );
+
+        let empty = mount_path("empty", &["Applications/", "Zed.app.zip"]);
+        let error = find_app_bundle(&empty).await.unwrap_err();
+        assert!(matches!(
+            UpdateError::from_error(&error),
+            UpdateError::Mount(message) if message.contains("doesn't contain an app")
+        ));
+
+        let ambiguous = mount_path("ambiguous", &["Zed.app/", "Zed Preview.app/"]);
+        let error = find_app_bundle(&ambiguous).await.unwrap_err();
+        assert!(matches!(
+            UpdateError::from_error(&error),
+            UpdateError::Mount(message)
+                if message.contains("\"Zed Preview.app\", \"Zed.app\"")
+        ));
+    }
+
+    #[gpui::test]
+    async fn test_swap_in_app() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let app_path = temp_dir.path().join("Zed.app");
+        let new_app_path = temp_dir.path().join("mount/Zed.app");
+        std::fs::create_dir_all(app_path.join("Contents")).unwrap();
+        std::fs::create_dir_all(new_app_path.join("Contents")).unwrap();
+        std::fs::write(app_path.join("Contents/version"), "old").unwrap();
+        std::fs::write(app_path.join("Contents/removed"), "").unwrap();
+        std::fs::write(new_app_path.join("Contents/version"), "new").unwrap();
+
+        swap_in_app(&new_app_path, &app_path).await.unwrap();
+        let read = |path: &Path| std::fs::read_to_string(path.join("Contents/version")).unwrap();
+        let backup_app_path = temp_dir.path().join("Zed.app.backup");
+        assert_eq!(read(&app_path), "new");
+        assert!(!app_path.join("Contents/removed").exists());
+        assert_eq!(read(&backup_app_path), "old");
+        assert!(!staged_app_path(&app_path).unwrap().exists());
+
+        restore_app_backup(&app_path).await.unwrap();
+        assert_eq!(read(&app_path), "old");
+        assert!(!backup_app_path.exists());
+        // Without a backup, there is nothing to restore.
+        restore_app_backup(&app_path).await.unwrap();
+        assert_eq!(read(&app_path), "old");
+
+        // A failed copy leaves the app untouched.
+        let missing_app_path = temp_dir.path().join("missing/Zed.app");
+        assert!(swap_in_app(&missing_app_path, &app_path).await.is_err());
+        assert_eq!(read(&app_path), "old");
+        assert!(!staged_app_path(&app_path).unwrap().exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[gpui::test]
+    async fn test_install_tarball() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let install_dir = temp_dir.path().join("zed.app");
+        std::fs::create_dir_all(install_dir.join("bin")).unwrap();
+        std::fs::write(install_dir.join("bin/zed"), "old").unwrap();
+        std::fs::write(install_dir.join("removed"), "").unwrap();
+        let running_app_path = install_dir.join("bin/zed");
+
+        let create_tarball = |name: &str, dirs: &[&str]| {
+            let contents_dir = temp_dir.path().join(format!("{name}-contents"));
+            for dir in dirs {
+                std::fs::create_dir_all(contents_dir.join(dir).join("bin")).unwrap();
+                std::fs::write(contents_dir.join(dir).join("bin/zed"), "new").unwrap();
+            }
+            let tarball_path = temp_dir.path().join(format!("{name}.tar.gz"));
+            let status = std::process::Command::new("tar")
+                .arg("-czf")
+                .arg(&tarball_path)
+                .arg("-C")
+                .arg(&contents_dir)
+                .args(dirs)
+                .status()
+                .unwrap();
+            assert!(status.success());
+            tarball_path
+        };
+        let read = || std::fs::read_to_string(&running_app_path).unwrap();
+
+        // The installation must be the tarball's only directory.
+        let tarball_path = create_tarball("ambiguous", &["zed-a.app", "zed-b.app"]);
+        assert!(install_tarball(&tarball_path, &running_app_path)
+            .await
+            .is_err());
+        assert_eq!(read(), "old");
+
+        // Neither a directory that isn't a Zed installation, nor one without
+        // the running binary, is replaced.
+        let is_unrecognized_installation = |error: anyhow::Error| {
+            matches!(
+                UpdateError::from_error(&error),
+                UpdateError::CannotAutoUpdate(
+                    crate::CannotAutoUpdateReason::UnrecognizedInstallation
+                )
+            )
+        };
+        let local_dir = temp_dir.path().join(".local");
+        std::fs::create_dir_all(local_dir.join("bin")).unwrap();
+        std::fs::write(local_dir.join("bin/zed"), "old").unwrap();
+        let tarball_path = create_tarball("local", &["zed.app"]);
+        let error = install_tarball(&tarball_path, &local_dir.join("bin/zed"))
+            .await
+            .unwrap_err();
+        assert!(is_unrecognized_installation(error));
+        assert_eq!(
+            std::fs::read_to_string(local_dir.join("bin/zed")).unwrap(),
+            "old"
+        );
+        let error = install_tarball(&tarball_path, &install_dir.join("libexec/zed-editor"))
+            .await
+            .unwrap_err();
+        assert!(is_unrecognized_installation(error));
+        std::fs::create_dir_all(install_dir.join("libexec")).unwrap();
+        std::fs::write(install_dir.join("libexec/zed-editor"), "old").unwrap();
+        let error = install_tarball(&tarball_path, &install_dir.join("libexec/zed-editor"))
+            .await
+            .unwrap_err();
+        assert!(is_unrecognized_installation(error));
+        assert_eq!(read(), "old");
+        std::fs::remove_dir_all(install_dir.join("libexec")).unwrap();
+
+        let tarball_path = create_tarball("update", &["zed-preview.app"]);
+        install_tarball(&tarball_path, &running_app_path)
+            .await
+            .unwrap();
+        assert_eq!(read(), "new");
+        assert!(!install_dir.join("removed").exists());
+        // The staging directory, along with the previous installation, is
+        // cleaned up.
+        let leftovers = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".zed-auto-update")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_parse_team_identifier() {
+        let details = "Executable=/Volumes/Zed/Zed.app/Contents/MacOS/zed\n\
+            Identifier=dev.zed.Zed\n\
+            Format=app bundle with Mach-O thin (arm64)\n\
+            TeamIdentifier=MQ55VZLNZQ\n\
+            Sealed Resources version=2 rules=13 files=42\n";
+        assert_eq!(parse_team_identifier(details), Some("MQ55VZLNZQ"));
+        assert_eq!(
+            parse_team_identifier("Identifier=zed\nTeamIdentifier=not set\n"),
+            None
+        );
+        assert_eq!(
+            parse_team_identifier("code object is not signed at all"),
+            None
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[gpui::test]
+    async fn test_unsigned_bundle_fails_verification() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let app_path = temp_dir.path().join("Zed.app");
+        let executable_dir = app_path.join("Contents/MacOS");
+        std::fs::create_dir_all(&executable_dir).unwrap();
+        std::fs::write(executable_dir.join("zed"), "#!/bin/sh\n").unwrap();
+
+        let error = verify_code_signature(&app_path, EXPECTED_TEAM_ID)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            UpdateError::from_error(&error),
+            UpdateError::CodeSignature(_)
+        ));
+    }
+
+    #[gpui::test]
+    async fn test_retain_app_backup() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let app_path = temp_dir.path().join("Zed.app");
+        let cache_dir = temp_dir.path().join("previous-version");
+        let new_app_path = temp_dir.path().join("mount/Zed.app");
+        std::fs::create_dir_all(app_path.join("Contents")).unwrap();
+        std::fs::create_dir_all(new_app_path.join("Contents")).unwrap();
+        std::fs::write(app_path.join("Contents/version"), "old").unwrap();
+        std::fs::write(new_app_path.join("Contents/version"), "new").unwrap();
+        let read = |path: &Path| std::fs::read_to_string(path.join("Contents/version")).unwrap();
+
+        assert!(!retain_app_backup(&app_path, &cache_dir).await.unwrap());
+        swap_in_app(&new_app_path, &app_path).await.unwrap();
+        assert!(retain_app_backup(&app_path, &cache_dir).await.unwrap());
+        assert_eq!(read(&cache_dir.join("Zed.app")), "old");
+        assert!(!app_backup_path(&app_path).unwrap().exists());
+
+        restore_retained_app(&app_path, &cache_dir).await.unwrap();
+        assert_eq!(read(&app_path), "old");
+        assert!(!cache_dir.exists());
+        assert!(!app_backup_path(&app_path).unwrap().exists());
+        assert!(restore_retained_app(&app_path, &cache_dir).await.is_err());
+    }
+
+    #[test]
+    fn test_finish_install_script() {
+        let script = finish_install_script(
+            42,
+            Path::new(r"C:\Users\O'Brien\Zed"),
+            Path::new(r"C:\Users\O'Brien\Zed.update"),
+            Path::new(r"C:\Users\O'Brien\Zed.backup"),
+            Some(Path::new(r"C:\Users\O'Brien\Zed\Zed.exe")),
+        );
+        assert!(script.contains("Get-Process -Id 42 "));
+        assert!(script.contains(
+            r"Move-Item -LiteralPath 'C:\Users\O''Brien\Zed' -Destination 'C:\Users\O''Brien\Zed.backup'"
+        ));
+        assert!(script.contains(
+            r"Move-Item -LiteralPath 'C:\Users\O''Brien\Zed.update' -Destination 'C:\Users\O''Brien\Zed'"
+        ));
+        assert!(script.contains(r"Start-Process -FilePath 'C:\Users\O''Brien\Zed\Zed.exe'"));
+
+        let script = finish_install_script(
+            42,
+            Path::new(r"C:\Zed"),
+            Path::new(r"C:\Zed.update"),
+            Path::new(r"C:\Zed.backup"),
+            None,
+        );
+        assert!(!script.contains("Start-Process"));
+    }
+
+    #[test]
+    fn test_find_unwritable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let app_path = temp_dir.path().join("Zed.app");
+        let resources = app_path.join("Contents/Resources");
+        std::fs::create_dir_all(&resources).unwrap();
+        std::fs::write(app_path.join("Contents/Info.plist"), "").unwrap();
+        std::fs::write(resources.join("zed.icns"), "").unwrap();
+
+        assert_eq!(find_unwritable(&app_path, &|_| true), None);
+        assert_eq!(
+            find_unwritable(&app_path, &|path| !path.ends_with("zed.icns")),
+            Some(resources.join("zed.icns"))
+        );
+        assert_eq!(
+            find_unwritable(&app_path, &|path| path != app_path),
+            Some(app_path.clone())
+        );
+    }
+
+    #[test]
+    fn test_is_outside_applications() {
+        let home_dir = Path::new("/Users/someone");
+        assert!(!is_outside_applications(
+            Path::new("/Applications/Zed.app"),
+            home_dir
+        ));
+        assert!(!is_outside_applications(
+            Path::new("/Users/someone/Applications/Zed.app"),
+            home_dir
+        ));
+        assert!(is_outside_applications(
+            Path::new("/Users/someone/Downloads/Zed.app"),
+            home_dir
+        ));
+        assert!(is_outside_applications(
+            Path::new("/Users/someone/ApplicationsOld/Zed.app"),
+            home_dir
+        ));
+        assert!(is_outside_applications(
+            Path::new("/Users/another/Applications/Zed.app"),
+            home_dir
+        ));
+    }
+
+    #[test]
+    fn test_privileged_copy_script() {
+        assert_eq!(
+            privileged_copy_script(
+                Path::new("/Volumes/Zed/Zed.app"),
+                Path::new("/Applications/Zed's \"Apps\"/Zed.app"),
+            ),
+            r#"do shell script "/usr/bin/rsync -a --delete '/Volumes/Zed/Zed.app/' '/Applications/Zed'\\''s \"Apps\"/Zed.app'" with administrator privileges"#
+        );
+        assert_eq!(
+            copy_error("rsync: mkstemp failed: Permission denied (13)".into()),
+            UpdateError::PermissionDenied("rsync: mkstemp failed: Permission denied (13)".into())
+        );
+        assert_eq!(
+            copy_error("No space left on device".into()),
+            UpdateError::Copy("No space left on device".into())
+        );
+    }
+}
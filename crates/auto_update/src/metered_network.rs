@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+/// Reports whether the current network connection is one over which large
+/// downloads should be avoided, such as a personal hotspot or a network in
+/// Low Data Mode.
+pub trait MeteredNetworkDetector: Send + Sync {
+    fn is_metered(&self) -> bool;
+}
+
+/// Treats every connection as unmetered, for platforms that can't tell.
+pub struct UnmeteredNetwork;
+
+impl MeteredNetworkDetector for UnmeteredNetwork {
+    fn is_metered(&self) -> bool {
+        false
+    }
+}
+
+/// The detector for the current platform.
+pub fn platform_detector() -> Arc<dyn MeteredNetworkDetector> {
+    #[cfg(target_os = "macos")]
+    {
+        Arc::new(mac::PathMonitor::new())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Arc::new(UnmeteredNetwork)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod mac {
+    use super::MeteredNetworkDetector;
+    use block::ConcreteBlock;
+    use std::{
+        ffi::{c_char, c_void},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    type NwPathMonitor = *mut c_void;
+    type NwPath = *mut c_void;
+    type DispatchQueue = *mut c_void;
+
+    #[link(name = "Network", kind = "framework")]
+    extern "C" {
+        fn nw_path_monitor_create() -> NwPathMonitor;
+        fn nw_path_monitor_set_queue(monitor: NwPathMonitor, queue: DispatchQueue);
+        fn nw_path_monitor_set_update_handler(monitor: NwPathMonitor, handler: *const c_void);
+        fn nw_path_monitor_start(monitor: NwPathMonitor);
+        fn nw_path_monitor_cancel(monitor: NwPathMonitor);
+        fn nw_path_is_expensive(path: NwPath) -> bool;
+        fn nw_path_is_constrained(path: NwPath) -> bool;
+        fn nw_release(object: *mut c_void);
+    }
+
+    extern "C" {
+        fn dispatch_queue_create(label: *const c_char, attr: *const c_void) -> DispatchQueue;
+        fn dispatch_release(object: DispatchQueue);
+    }
+
+    /// Tracks the system's current network path, which is marked expensive
+    /// on cellular and hotspot connections, and constrained in Low Data Mode.
+    pub struct PathMonitor {
+        monitor: NwPathMonitor,
+        queue: DispatchQueue,
+        is_metered: Arc<AtomicBool>,
+    }
+
+    // The monitor is only touched through the Network framework, which is
+    // thread-safe, and its updates are delivered on its own dispatch queue.
+    unsafe impl Send for PathMonitor {}
+    unsafe impl Sync for PathMonitor {}
+
+    impl PathMonitor {
+        pub fn new() -> Self {
+            let is_metered = Arc::new(AtomicBool::new(false));
+            unsafe {
+                let monitor = nw_path_monitor_create();
+                let queue = dispatch_queue_create(
+                    b"dev.zed.auto-update.path-monitor\0".as_ptr().cast(),
+                    std::ptr::null(),
+                );
+                let handler = ConcreteBlock::new({
+                    let is_metered = is_metered.clone();
+                    move |path: NwPath| {
+                        let metered = nw_path_is_expensive(path) || nw_path_is_constrained(path);
+                        is_metered.store(metered, Ordering::Relaxed);
+                    }
+                })
+                .copy();
+                nw_path_monitor_set_queue(monitor, queue);
+                nw_path_monitor_set_update_handler(monitor, &*handler as *const _ as *const c_void);
+                nw_path_monitor_start(monitor);
+                Self {
+                    monitor,
+                    queue,
+                    is_metered,
+                }
+            }
+        }
+    }
+
+    impl MeteredNetworkDetector for PathMonitor {
+        fn is_metered(&self) -> bool {
+            self.is_metered.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Drop for PathMonitor {
+        fn drop(&mut self) {
+            unsafe {
+                nw_path_monitor_cancel(self.monitor);
+                nw_release(self.monitor);
+                dispatch_release(self.queue);
+            }
+        }
+    }
+}
@@ -1,13 +1,21 @@
+use crate::AutoUpdater;
 use gpui::{
-    div, DismissEvent, EventEmitter, InteractiveElement, IntoElement, ParentElement, Render,
-    SemanticVersion, StatefulInteractiveElement, Styled, ViewContext,
+    div, prelude::FluentBuilder, DismissEvent, EventEmitter, InteractiveElement, IntoElement,
+    ParentElement, Render, SemanticVersion, SharedString, StatefulInteractiveElement, Styled,
+    ViewContext,
 };
 use menu::Cancel;
 use release_channel::ReleaseChannel;
-use workspace::ui::{h_flex, v_flex, Icon, IconName, Label, StyledExt};
+use workspace::ui::{
+    h_flex, v_flex, Color, Icon, IconName, Label, LabelCommon, LabelSize, StyledExt,
+};
 
 pub struct UpdateNotification {
-    version: SemanticVersion,
+    version: SharedString,
+    release_notes: Option<SharedString>,
+    /// Whether the update has been downloaded but not installed yet, rather
+    /// than installed.
+    ready_to_install: bool,
 }
 
 impl EventEmitter<DismissEvent> for UpdateNotification {}
@@ -23,10 +31,11 @@ impl Render for UpdateNotification {
             .child(
                 h_flex()
                     .justify_between()
-                    .child(Label::new(format!(
-                        "Updated to {app_name} {}",
-                        self.version
-                    )))
+                    .child(Label::new(if self.ready_to_install {
+                        format!("{app_name} {} is ready to install", self.version)
+                    } else {
+                        format!("Updated to {app_name} {}", self.version)
+                    }))
                     .child(
                         div()
                             .id("cancel")
@@ -35,25 +44,96 @@ impl Render for UpdateNotification {
                             .on_click(cx.listener(|this, _, cx| this.dismiss(&menu::Cancel, cx))),
                     ),
             )
+            .children(self.release_notes.clone().map(|release_notes| {
+                div()
+                    .id("release-notes")
+                    .max_h_48()
+                    .overflow_y_scroll()
+                    .child(
+                        Label::new(release_notes)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+            }))
+            .when(self.ready_to_install, |this| {
+                this.child(
+                    div()
+                        .id("install")
+                        .child(Label::new("Install and Restart"))
+                        .cursor_pointer()
+                        .on_click(cx.listener(|this, _, cx| {
+                            crate::install_and_restart(cx);
+                            this.dismiss(&menu::Cancel, cx)
+                        })),
+                )
+            })
             .child(
                 div()
                     .id("notes")
                     .child(Label::new("View the release notes"))
                     .cursor_pointer()
                     .on_click(cx.listener(|this, _, cx| {
-                        crate::view_release_notes(&Default::default(), cx);
+                        // Nightly versions are commit SHAs, whose notes are
+                        // the changelog's.
+                        match this.version.parse() {
+                            Ok(version) => crate::view_release_notes_for(version, cx),
+                            Err(_) => crate::view_release_notes(&Default::default(), cx),
+                        };
+                        this.dismiss(&menu::Cancel, cx)
+                    })),
+            )
+            .child(
+                div()
+                    .id("skip")
+                    .child(Label::new("Skip this version"))
+                    .cursor_pointer()
+                    .on_click(cx.listener(|this, _, cx| {
+                        cx.dispatch_action(Box::new(crate::SkipVersion));
                         this.dismiss(&menu::Cancel, cx)
                     })),
             )
+            .when(!self.ready_to_install, |this| {
+                this.child(
+                    div()
+                        .id("mute")
+                        .child(Label::new("Don't show this again"))
+                        .cursor_pointer()
+                        .on_click(cx.listener(|this, _, cx| this.mute(cx))),
+                )
+            })
     }
 }
 
 impl UpdateNotification {
-    pub fn new(version: SemanticVersion) -> Self {
-        Self { version }
+    pub fn new(version: SemanticVersion, release_notes: Option<String>) -> Self {
+        Self {
+            version: version.to_string().into(),
+            release_notes: release_notes.map(Into::into),
+            ready_to_install: false,
+        }
+    }
+
+    /// A notification offering to install an update that has been downloaded.
+    pub fn ready_to_install(version: SharedString, release_notes: Option<String>) -> Self {
+        Self {
+            version,
+            release_notes: release_notes.map(Into::into),
+            ready_to_install: true,
+        }
     }
 
     pub fn dismiss(&mut self, _: &Cancel, cx: &mut ViewContext<Self>) {
         cx.emit(DismissEvent);
     }
+
+    /// Dismisses the notification, and stops showing it after later updates.
+    fn mute(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(updater) = AutoUpdater::get(cx) {
+            updater
+                .read(cx)
+                .mute_update_notifications(cx)
+                .detach_and_log_err(cx);
+        }
+        self.dismiss(&menu::Cancel, cx)
+    }
 }
@@ -505,6 +505,12 @@ impl Client {
     pub fn global(cx: &AppContext) -> Arc<Self> {
         cx.global::<GlobalClient>().0.clone()
     }
+
+    pub fn try_global(cx: &AppContext) -> Option<Arc<Self>> {
+        cx.try_global::<GlobalClient>()
+            .map(|global_client| global_client.0.clone())
+    }
+
     pub fn set_global(client: Arc<Client>, cx: &mut AppContext) {
         cx.set_global(GlobalClient(client))
     }
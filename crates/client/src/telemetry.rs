@@ -14,9 +14,9 @@ use std::io::Write;
 use std::{env, mem, path::PathBuf, sync::Arc, time::Duration};
 use sysinfo::{CpuRefreshKind, MemoryRefreshKind, Pid, ProcessRefreshKind, RefreshKind, System};
 use telemetry_events::{
-    ActionEvent, AppEvent, AssistantEvent, AssistantKind, CallEvent, CopilotEvent, CpuEvent,
-    EditEvent, EditorEvent, Event, EventRequestBody, EventWrapper, ExtensionEvent, MemoryEvent,
-    SettingEvent,
+    ActionEvent, AppEvent, AssistantEvent, AssistantKind, AutoUpdateEvent, CallEvent, CopilotEvent,
+    CpuEvent, EditEvent, EditorEvent, Event, EventRequestBody, EventWrapper, ExtensionEvent,
+    MemoryEvent, SettingEvent,
 };
 use tempfile::NamedTempFile;
 use util::http::{self, HttpClient, HttpClientWithUrl, Method};
@@ -334,6 +334,23 @@ impl Telemetry {
         }))
     }
 
+    pub fn report_auto_update_event(
+        self: &Arc<Self>,
+        stage: &'static str,
+        from_version: String,
+        to_version: Option<String>,
+        release_channel: &'static str,
+        error_category: Option<&'static str>,
+    ) {
+        self.report_event(Event::AutoUpdate(AutoUpdateEvent {
+            stage: stage.to_string(),
+            from_version,
+            to_version,
+            release_channel: release_channel.to_string(),
+            error_category: error_category.map(str::to_string),
+        }))
+    }
+
     pub fn log_edit_event(self: &Arc<Self>, environment: &'static str) {
         let mut state = self.state.lock();
         let period_data = state.event_coalescer.log_event(environment);
@@ -15,8 +15,9 @@ use serde::{Serialize, Serializer};
 use sha2::{Digest, Sha256};
 use std::sync::{Arc, OnceLock};
 use telemetry_events::{
-    ActionEvent, AppEvent, AssistantEvent, CallEvent, CopilotEvent, CpuEvent, EditEvent,
-    EditorEvent, Event, EventRequestBody, EventWrapper, ExtensionEvent, MemoryEvent, SettingEvent,
+    ActionEvent, AppEvent, AssistantEvent, AutoUpdateEvent, CallEvent, CopilotEvent, CpuEvent,
+    EditEvent, EditorEvent, Event, EventRequestBody, EventWrapper, ExtensionEvent, MemoryEvent,
+    SettingEvent,
 };
 
 pub fn router() -> Router {
@@ -329,6 +330,16 @@ pub async fn post_events(
                 &request_body,
                 first_event_at,
             )),
+            Event::AutoUpdate(event) => {
+                to_upload
+                    .auto_update_events
+                    .push(AutoUpdateEventRow::from_event(
+                        event.clone(),
+                        &wrapper,
+                        &request_body,
+                        first_event_at,
+                    ))
+            }
             Event::Extension(event) => {
                 let metadata = app
                     .db
@@ -368,6 +379,7 @@ struct ToUpload {
     extension_events: Vec<ExtensionEventRow>,
     edit_events: Vec<EditEventRow>,
     action_events: Vec<ActionEventRow>,
+    auto_update_events: Vec<AutoUpdateEventRow>,
 }
 
 impl ToUpload {
@@ -443,6 +455,15 @@ impl ToUpload {
             .await
             .with_context(|| format!("failed to upload to table '{ACTION_EVENTS_TABLE}'"))?;
 
+        const AUTO_UPDATE_EVENTS_TABLE: &str = "auto_update_events";
+        Self::upload_to_table(
+            AUTO_UPDATE_EVENTS_TABLE,
+            &self.auto_update_events,
+            clickhouse_client,
+        )
+        .await
+        .with_context(|| format!("failed to upload to table '{AUTO_UPDATE_EVENTS_TABLE}'"))?;
+
         Ok(())
     }
 
@@ -1053,3 +1074,56 @@ impl ActionEventRow {
         }
     }
 }
+
+#[derive(Serialize, Debug, clickhouse::Row)]
+pub struct AutoUpdateEventRow {
+    // AppInfoBase
+    app_version: String,
+    major: Option<i32>,
+    minor: Option<i32>,
+    patch: Option<i32>,
+    release_channel: String,
+
+    // ClientEventBase
+    installation_id: Option<String>,
+    session_id: Option<String>,
+    is_staff: Option<bool>,
+    time: i64,
+
+    // AutoUpdateEventRow
+    stage: String,
+    from_version: String,
+    to_version: Option<String>,
+    update_channel: String,
+    error_category: Option<String>,
+}
+
+impl AutoUpdateEventRow {
+    fn from_event(
+        event: AutoUpdateEvent,
+        wrapper: &EventWrapper,
+        body: &EventRequestBody,
+        first_event_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        let semver = body.semver();
+        let time =
+            first_event_at + chrono::Duration::milliseconds(wrapper.milliseconds_since_first_event);
+
+        Self {
+            app_version: body.app_version.clone(),
+            major: semver.map(|v| v.major() as i32),
+            minor: semver.map(|v| v.minor() as i32),
+            patch: semver.map(|v| v.patch() as i32),
+            release_channel: body.release_channel.clone().unwrap_or_default(),
+            installation_id: body.installation_id.clone(),
+            session_id: body.session_id.clone(),
+            is_staff: body.is_staff,
+            time: time.timestamp_millis(),
+            stage: event.stage,
+            from_version: event.from_version,
+            to_version: event.to_version,
+            update_channel: event.release_channel,
+            error_category: event.error_category,
+        }
+    }
+}
@@ -638,13 +638,22 @@ impl CollabTitlebarItem {
             client::Status::UpgradeRequired => {
                 let auto_updater = auto_update::AutoUpdater::get(cx);
                 let label = match auto_updater.map(|auto_update| auto_update.read(cx).status()) {
-                    Some(AutoUpdateStatus::Updated) => "Please restart Zed to Collaborate",
+                    Some(AutoUpdateStatus::Updated)
+                    | Some(AutoUpdateStatus::PendingRestart)
+                    | Some(AutoUpdateStatus::RolledBack) => "Please restart Zed to Collaborate",
                     Some(AutoUpdateStatus::Installing)
-                    | Some(AutoUpdateStatus::Downloading)
+                    | Some(AutoUpdateStatus::Downloading { .. })
+                    | Some(AutoUpdateStatus::ReadyToInstall)
+                    | Some(AutoUpdateStatus::WaitingForIdle)
                     | Some(AutoUpdateStatus::Checking) => "Updating...",
-                    Some(AutoUpdateStatus::Idle) | Some(AutoUpdateStatus::Errored) | None => {
-                        "Please update Zed to Collaborate"
-                    }
+                    Some(AutoUpdateStatus::Idle)
+                    | Some(AutoUpdateStatus::UpdateAvailable)
+                    | Some(AutoUpdateStatus::AwaitingConfirmation { .. })
+                    | Some(AutoUpdateStatus::UpdateAvailableButBlocked)
+                    | Some(AutoUpdateStatus::CannotAutoUpdate(_))
+                    | Some(AutoUpdateStatus::Errored(_))
+                    | Some(AutoUpdateStatus::Offline)
+                    | None => "Please update Zed to Collaborate",
                 };
 
                 Some(
@@ -652,8 +661,13 @@ impl CollabTitlebarItem {
                         .label_size(LabelSize::Small)
                         .on_click(|_, cx| {
                             if let Some(auto_updater) = auto_update::AutoUpdater::get(cx) {
-                                if auto_updater.read(cx).status() == AutoUpdateStatus::Updated {
-                                    workspace::restart(&Default::default(), cx);
+                                if matches!(
+                                    auto_updater.read(cx).status(),
+                                    AutoUpdateStatus::Updated
+                                        | AutoUpdateStatus::PendingRestart
+                                        | AutoUpdateStatus::RolledBack
+                                ) {
+                                    auto_update::restart_to_update(cx);
                                     return;
                                 }
                             }
@@ -199,6 +199,7 @@ impl App {
 type Handler = Box<dyn FnMut(&mut AppContext) -> bool + 'static>;
 type Listener = Box<dyn FnMut(&dyn Any, &mut AppContext) -> bool + 'static>;
 type KeystrokeObserver = Box<dyn FnMut(&KeystrokeEvent, &mut WindowContext) + 'static>;
+type MouseInputObserver = Box<dyn FnMut(&mut WindowContext) + 'static>;
 type QuitHandler = Box<dyn FnOnce(&mut AppContext) -> LocalBoxFuture<'static, ()> + 'static>;
 type ReleaseListener = Box<dyn FnOnce(&mut dyn Any, &mut AppContext) + 'static>;
 type NewViewListener = Box<dyn FnMut(AnyView, &mut WindowContext) + 'static>;
@@ -237,6 +238,7 @@ pub struct AppContext {
     // TypeId is the type of the event that the listener callback expects
     pub(crate) event_listeners: SubscriberSet<EntityId, (TypeId, Listener)>,
     pub(crate) keystroke_observers: SubscriberSet<(), KeystrokeObserver>,
+    pub(crate) mouse_input_observers: SubscriberSet<(), MouseInputObserver>,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
@@ -299,6 +301,7 @@ impl AppContext {
                 event_listeners: SubscriberSet::new(),
                 release_listeners: SubscriberSet::new(),
                 keystroke_observers: SubscriberSet::new(),
+                mouse_input_observers: SubscriberSet::new(),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
                 layout_id_buffer: Default::default(),
@@ -1037,6 +1040,18 @@ impl AppContext {
         inner(&mut self.keystroke_observers, Box::new(f))
     }
 
+    /// Register a callback to be invoked when the mouse is moved, clicked or scrolled in any window.
+    /// Unlike [`Self::observe_keystrokes`], this fires before the event is dispatched, so it is
+    /// invoked even if the event's propagation is stopped.
+    pub fn observe_mouse_input(
+        &mut self,
+        f: impl FnMut(&mut WindowContext) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.mouse_input_observers.insert((), Box::new(f));
+        activate();
+        subscription
+    }
+
     /// Register key bindings.
     pub fn bind_keys(&mut self, bindings: impl IntoIterator<Item = KeyBinding>) {
         self.keymap.borrow_mut().add_bindings(bindings);
@@ -787,6 +787,15 @@ impl<'a> WindowContext<'a> {
             });
     }
 
+    fn dispatch_mouse_input_observers(&mut self) {
+        self.mouse_input_observers
+            .clone()
+            .retain(&(), move |callback| {
+                (callback)(self);
+                true
+            });
+    }
+
     pub(crate) fn clear_pending_keystrokes(&mut self) {
         self.window
             .rendered_frame
@@ -1256,6 +1265,15 @@ impl<'a> WindowContext<'a> {
         };
 
         if let Some(any_mouse_event) = event.mouse_event() {
+            if matches!(
+                event,
+                PlatformInput::MouseDown(_)
+                    | PlatformInput::MouseUp(_)
+                    | PlatformInput::MouseMove(_)
+                    | PlatformInput::ScrollWheel(_)
+            ) {
+                self.dispatch_mouse_input_observers();
+            }
             self.dispatch_mouse_event(any_mouse_event);
         } else if let Some(any_key_event) = event.keyboard_event() {
             self.dispatch_key_event(any_key_event);
@@ -63,6 +63,7 @@ pub enum Event {
     Extension(ExtensionEvent),
     Edit(EditEvent),
     Action(ActionEvent),
+    AutoUpdate(AutoUpdateEvent),
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -135,3 +136,12 @@ pub struct ExtensionEvent {
 pub struct AppEvent {
     pub operation: String,
 }
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AutoUpdateEvent {
+    pub stage: String,
+    pub from_version: String,
+    pub to_version: Option<String>,
+    pub release_channel: String,
+    pub error_category: Option<String>,
+}
@@ -39,6 +39,8 @@ lazy_static::lazy_static! {
     pub static ref EXTENSIONS_DIR: PathBuf = SUPPORT_DIR.join("extensions");
     pub static ref LANGUAGES_DIR: PathBuf = SUPPORT_DIR.join("languages");
     pub static ref COPILOT_DIR: PathBuf = SUPPORT_DIR.join("copilot");
+    pub static ref UPDATES_DIR: PathBuf = SUPPORT_DIR.join("updates");
+    pub static ref PREVIOUS_VERSION_DIR: PathBuf = SUPPORT_DIR.join("previous-version");
     pub static ref DEFAULT_PRETTIER_DIR: PathBuf = SUPPORT_DIR.join("prettier");
     pub static ref DB_DIR: PathBuf = SUPPORT_DIR.join("db");
     pub static ref CRASHES_DIR: PathBuf = if cfg!(target_os = "macos") {
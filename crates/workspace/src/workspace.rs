@@ -4905,6 +4905,19 @@ pub fn join_in_room_project(
 }
 
 pub fn restart(_: &Restart, cx: &mut AppContext) {
+    let prepare = prepare_to_restart(cx);
+    cx.spawn(|cx| async move {
+        if prepare.await? {
+            cx.update(|cx| cx.restart())?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Asks the user to confirm restarting, if `confirm_quit` is set, and to save
+/// any unsaved changes. Resolves to whether the restart should go ahead.
+pub fn prepare_to_restart(cx: &mut AppContext) -> Task<Result<bool>> {
     let should_confirm = WorkspaceSettings::get_global(cx).confirm_quit;
     let mut workspace_windows = cx
         .windows()
@@ -4934,7 +4947,7 @@ pub fn restart(_: &Restart, cx: &mut AppContext) {
         if let Some(prompt) = prompt {
             let answer = prompt.await?;
             if answer != 0 {
-                return Ok(());
+                return Ok(false);
             }
         }
 
@@ -4944,14 +4957,13 @@ pub fn restart(_: &Restart, cx: &mut AppContext) {
                 workspace.prepare_to_close(true, cx)
             }) {
                 if !should_close.await? {
-                    return Ok(());
+                    return Ok(false);
                 }
             }
         }
 
-        cx.update(|cx| cx.restart())
+        Ok(true)
     })
-    .detach_and_log_err(cx);
 }
 
 fn parse_pixel_position_env_var(value: &str) -> Option<Point<DevicePixels>> {
@@ -4,6 +4,7 @@ mod open_listener;
 
 pub use app_menus::*;
 use assistant::AssistantPanel;
+use auto_update::AutoUpdater;
 use breadcrumbs::Breadcrumbs;
 use client::ZED_URL_SCHEME;
 use collections::VecDeque;
@@ -28,7 +29,7 @@ use settings::{
     initial_local_settings_content, initial_tasks_content, watch_config_file, KeymapFile, Settings,
     SettingsStore, DEFAULT_KEYMAP_PATH,
 };
-use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
+use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc, time::SystemTime};
 use task::static_source::{StaticSource, TrackedFile};
 use theme::ActiveTheme;
 use workspace::notifications::NotificationId;
@@ -457,7 +458,17 @@ fn about(_: &mut Workspace, _: &About, cx: &mut gpui::ViewContext<Workspace>) {
     let release_channel = ReleaseChannel::global(cx).display_name();
     let version = env!("CARGO_PKG_VERSION");
     let message = format!("{release_channel} {version}");
-    let detail = AppCommitSha::try_global(cx).map(|sha| sha.0.clone());
+    let last_check = AutoUpdater::get(cx)
+        .and_then(|updater| updater.read(cx).last_checked_at())
+        .map(|last_checked_at| {
+            auto_update::describe_last_check(last_checked_at, SystemTime::now())
+        });
+    let detail = AppCommitSha::try_global(cx)
+        .map(|sha| sha.0.clone())
+        .into_iter()
+        .chain(last_check)
+        .collect::<Vec<_>>();
+    let detail = (!detail.is_empty()).then(|| detail.join("\n"));
 
     let prompt = cx.prompt(PromptLevel::Info, &message, detail.as_deref(), &["OK"]);
     cx.foreground_executor()